@@ -0,0 +1,395 @@
+use std::pin::Pin;
+use std::time::Duration;
+
+use anyhow::{ anyhow, Context, Result };
+use async_trait::async_trait;
+use eventsource_stream::Eventsource;
+use futures_util::{ Stream, StreamExt };
+use reqwest::Client as HttpClient;
+use serde::Deserialize;
+use serde_json::{ json, Value };
+
+use crate::config::{ ClientConfig, Config, ExtraConfig };
+
+/// Everything a [`Client`] needs to build a chat-completion request.
+#[derive(Debug, Clone)]
+pub struct SendData {
+    pub model: String,
+    pub messages: Vec<Value>,
+    pub max_tokens: u32,
+    pub temperature: f32,
+    /// Tool specs (as OpenAI `{"type": "function", "function": {...}}` blocks)
+    /// the model is allowed to call. Empty means no tool calling.
+    pub tools: Vec<Value>,
+}
+
+/// A single `choices[0].message.tool_calls[n]` entry the model asked us to run.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub function: ToolCallFunction,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolCallFunction {
+    pub name: String,
+    pub arguments: String,
+}
+
+/// The result of a completed, non-streamed chat request.
+#[derive(Debug, Clone)]
+pub enum Reply {
+    /// A plain text answer.
+    Message(String),
+    /// The model wants one or more local tools run before it can continue.
+    ToolCalls(Vec<ToolCall>),
+}
+
+/// A fragment of an in-progress streamed reply, yielded as it arrives.
+pub type TokenStream = Pin<Box<dyn Stream<Item = Result<String>> + Send>>;
+
+/// A provider capable of turning a [`SendData`] request into a reply.
+///
+/// Implementations hide away how a given backend authenticates and where it
+/// lives, so callers never talk to `reqwest` directly.
+#[async_trait]
+pub trait Client: Send + Sync {
+    async fn send_message(&self, data: SendData) -> Result<Reply>;
+
+    /// Same as [`Client::send_message`], but yields the reply incrementally
+    /// as `data: {...}` SSE frames arrive instead of waiting for the full body.
+    /// Tool calling is not supported over this path.
+    async fn send_message_stream(&self, data: SendData) -> Result<TokenStream>;
+}
+
+fn build_http_client(extra: &ExtraConfig) -> Result<HttpClient> {
+    let mut builder = HttpClient::builder();
+
+    if let Some(secs) = extra.connect_timeout {
+        builder = builder.connect_timeout(Duration::from_secs(secs));
+    }
+
+    if let Some(proxy) = &extra.proxy {
+        builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+    }
+
+    Ok(builder.build()?)
+}
+
+/// Adds `"tools"` to an otherwise-built request payload, unless `data.tools`
+/// is empty (in which case omitting the key avoids confusing providers that
+/// treat `tools: []` as "tool calling requested, with no tools available").
+fn with_tools(mut payload: Value, tools: &[Value]) -> Value {
+    if !tools.is_empty() {
+        payload["tools"] = json!(tools);
+    }
+    payload
+}
+
+async fn extract_reply(res: reqwest::Response) -> Result<Reply> {
+    let status = res.status();
+    if !status.is_success() {
+        let body = res.text().await.unwrap_or_default();
+        anyhow::bail!("API error: {} - {}", status, body);
+    }
+
+    let value: Value = res.json().await.context("Failed to parse response")?;
+    let message = &value["choices"][0]["message"];
+
+    if let Some(tool_calls) = message["tool_calls"].as_array() {
+        let calls = tool_calls
+            .iter()
+            .cloned()
+            .map(serde_json::from_value)
+            .collect::<std::result::Result<Vec<ToolCall>, _>>()
+            .context("Failed to parse tool_calls")?;
+        return Ok(Reply::ToolCalls(calls));
+    }
+
+    let content = message["content"].as_str().unwrap_or("No reply found").to_string();
+
+    Ok(Reply::Message(content.trim().to_string()))
+}
+
+/// Sends an already-built `"stream": true` request and turns the SSE body
+/// into a [`TokenStream`] of `choices[0].delta.content` fragments, stopping
+/// at the `data: [DONE]` sentinel. Frames with no `content` delta (e.g. the
+/// initial role-only frame) are silently skipped.
+async fn stream_reply(req: reqwest::RequestBuilder) -> Result<TokenStream> {
+    let res = req.send().await?;
+
+    let status = res.status();
+    if !status.is_success() {
+        let body = res.text().await.unwrap_or_default();
+        anyhow::bail!("API error: {} - {}", status, body);
+    }
+
+    let events = res.bytes_stream().eventsource();
+
+    let stream = futures_util::stream::unfold(events, |mut events| async move {
+        while let Some(event) = events.next().await {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    return Some((Err(anyhow!(e)), events));
+                }
+            };
+
+            if event.data == "[DONE]" {
+                return None;
+            }
+
+            let delta: Value = match serde_json::from_str(&event.data) {
+                Ok(delta) => delta,
+                Err(e) => {
+                    return Some((Err(anyhow!(e)), events));
+                }
+            };
+
+            if let Some(content) = delta["choices"][0]["delta"]["content"].as_str() {
+                return Some((Ok(content.to_string()), events));
+            }
+        }
+
+        None
+    });
+
+    Ok(Box::pin(stream))
+}
+
+/// Talks to the real OpenAI API (or any `api_base` override of it).
+pub struct OpenAiClient {
+    http: HttpClient,
+    api_key: String,
+    api_base: String,
+}
+
+impl OpenAiClient {
+    pub fn new(config: &ClientConfig) -> Result<Self> {
+        let api_key = config.api_key
+            .clone()
+            .ok_or_else(|| anyhow!("client '{}' is missing api_key", config.name))?;
+        let api_base = config.api_base
+            .clone()
+            .unwrap_or_else(|| "https://api.openai.com/v1".to_string());
+
+        Ok(Self { http: build_http_client(&config.extra)?, api_key, api_base })
+    }
+}
+
+#[async_trait]
+impl Client for OpenAiClient {
+    async fn send_message(&self, data: SendData) -> Result<Reply> {
+        let url = format!("{}/chat/completions", self.api_base.trim_end_matches('/'));
+        let payload = with_tools(
+            json!({
+            "model": data.model,
+            "messages": data.messages,
+            "max_tokens": data.max_tokens,
+            "temperature": data.temperature,
+        }),
+            &data.tools
+        );
+
+        let res = self.http.post(&url).bearer_auth(&self.api_key).json(&payload).send().await?;
+        extract_reply(res).await
+    }
+
+    async fn send_message_stream(&self, data: SendData) -> Result<TokenStream> {
+        let url = format!("{}/chat/completions", self.api_base.trim_end_matches('/'));
+        let payload =
+            json!({
+            "model": data.model,
+            "messages": data.messages,
+            "max_tokens": data.max_tokens,
+            "temperature": data.temperature,
+            "stream": true,
+        });
+
+        let req = self.http.post(&url).bearer_auth(&self.api_key).json(&payload);
+        stream_reply(req).await
+    }
+}
+
+/// Talks to an Azure OpenAI deployment, where the model lives in the URL
+/// rather than the request body and auth goes through `api-key`.
+pub struct AzureOpenAiClient {
+    http: HttpClient,
+    api_key: String,
+    api_base: String,
+    api_version: String,
+}
+
+impl AzureOpenAiClient {
+    pub fn new(config: &ClientConfig) -> Result<Self> {
+        let api_key = config.api_key
+            .clone()
+            .ok_or_else(|| anyhow!("client '{}' is missing api_key", config.name))?;
+        let api_base = config.api_base
+            .clone()
+            .ok_or_else(|| anyhow!("client '{}' is missing api_base", config.name))?;
+
+        Ok(Self {
+            http: build_http_client(&config.extra)?,
+            api_key,
+            api_base,
+            api_version: "2024-02-01".to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl Client for AzureOpenAiClient {
+    async fn send_message(&self, data: SendData) -> Result<Reply> {
+        let url = format!(
+            "{}/openai/deployments/{}/chat/completions?api-version={}",
+            self.api_base.trim_end_matches('/'),
+            data.model,
+            self.api_version
+        );
+        let payload = with_tools(
+            json!({
+            "messages": data.messages,
+            "max_tokens": data.max_tokens,
+            "temperature": data.temperature,
+        }),
+            &data.tools
+        );
+
+        let res = self.http.post(&url).header("api-key", &self.api_key).json(&payload).send().await?;
+        extract_reply(res).await
+    }
+
+    async fn send_message_stream(&self, data: SendData) -> Result<TokenStream> {
+        let url = format!(
+            "{}/openai/deployments/{}/chat/completions?api-version={}",
+            self.api_base.trim_end_matches('/'),
+            data.model,
+            self.api_version
+        );
+        let payload =
+            json!({
+            "messages": data.messages,
+            "max_tokens": data.max_tokens,
+            "temperature": data.temperature,
+            "stream": true,
+        });
+
+        let req = self.http.post(&url).header("api-key", &self.api_key).json(&payload);
+        stream_reply(req).await
+    }
+}
+
+/// Talks to anything that speaks the OpenAI chat-completions shape, at an
+/// arbitrary `api_base` (local model servers, other hosted providers, ...).
+pub struct CompatibleClient {
+    http: HttpClient,
+    api_key: Option<String>,
+    api_base: String,
+}
+
+impl CompatibleClient {
+    pub fn new(config: &ClientConfig) -> Result<Self> {
+        let api_base = config.api_base
+            .clone()
+            .ok_or_else(|| anyhow!("client '{}' is missing api_base", config.name))?;
+
+        Ok(Self { http: build_http_client(&config.extra)?, api_key: config.api_key.clone(), api_base })
+    }
+}
+
+#[async_trait]
+impl Client for CompatibleClient {
+    async fn send_message(&self, data: SendData) -> Result<Reply> {
+        let url = format!("{}/chat/completions", self.api_base.trim_end_matches('/'));
+        let payload = with_tools(
+            json!({
+            "model": data.model,
+            "messages": data.messages,
+            "max_tokens": data.max_tokens,
+            "temperature": data.temperature,
+        }),
+            &data.tools
+        );
+
+        let mut req = self.http.post(&url);
+        if let Some(api_key) = &self.api_key {
+            req = req.bearer_auth(api_key);
+        }
+
+        let res = req.json(&payload).send().await?;
+        extract_reply(res).await
+    }
+
+    async fn send_message_stream(&self, data: SendData) -> Result<TokenStream> {
+        let url = format!("{}/chat/completions", self.api_base.trim_end_matches('/'));
+        let payload =
+            json!({
+            "model": data.model,
+            "messages": data.messages,
+            "max_tokens": data.max_tokens,
+            "temperature": data.temperature,
+            "stream": true,
+        });
+
+        let mut req = self.http.post(&url);
+        if let Some(api_key) = &self.api_key {
+            req = req.bearer_auth(api_key);
+        }
+
+        stream_reply(req.json(&payload)).await
+    }
+}
+
+/// Builds the concrete [`Client`] for a configured provider entry.
+pub fn build_client(config: &ClientConfig) -> Result<Box<dyn Client>> {
+    match config.kind.as_str() {
+        "openai" => Ok(Box::new(OpenAiClient::new(config)?)),
+        "azure" | "azure-openai" => Ok(Box::new(AzureOpenAiClient::new(config)?)),
+        "openai-compatible" | "compatible" => Ok(Box::new(CompatibleClient::new(config)?)),
+        other => Err(anyhow!("unknown client type '{}' (expected openai, azure or openai-compatible)", other)),
+    }
+}
+
+/// Splits a `--model` value of the form `client:model` into its parts.
+/// A value with no `:` has no client prefix and should fall back to the
+/// default (`AI_API_KEY`-backed) client.
+pub fn parse_model_selector(spec: &str) -> (Option<&str>, &str) {
+    match spec.split_once(':') {
+        Some((client, model)) => (Some(client), model),
+        None => (None, spec),
+    }
+}
+
+/// Resolves a `--model` value into the client that should handle the
+/// request plus the bare model name to send it.
+///
+/// A value of the form `client:model` (e.g. `azure1:gpt-4o`) looks up
+/// `client` in the config file. A bare model name (e.g. `gpt-4o-mini`) falls
+/// back to a plain OpenAI client built from `AI_API_KEY`, preserving the
+/// tool's original behavior.
+pub fn resolve_client(config: &Config, model_spec: &str) -> Result<(Box<dyn Client>, String)> {
+    let (client_name, model) = parse_model_selector(model_spec);
+
+    match client_name {
+        Some(name) => {
+            let client_config = config
+                .find_client(name)
+                .with_context(|| format!("No client named '{}' in config", name))?;
+            Ok((build_client(client_config)?, model.to_string()))
+        }
+        None => {
+            let api_key = std::env
+                ::var("AI_API_KEY")
+                .context("API key not set. Please export AI_API_KEY before running.")?;
+            let fallback = ClientConfig {
+                kind: "openai".to_string(),
+                name: "default".to_string(),
+                api_key: Some(api_key),
+                api_base: None,
+                extra: Default::default(),
+            };
+            Ok((build_client(&fallback)?, model.to_string()))
+        }
+    }
+}
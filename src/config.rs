@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::{ Context, Result };
+use serde::Deserialize;
+
+/// A single configured provider, as read from `~/.config/ai-cli/config.toml`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ClientConfig {
+    /// One of "openai", "azure" or "openai-compatible".
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// The name used to select this client via `--model <name>:<model>`.
+    pub name: String,
+
+    pub api_key: Option<String>,
+
+    pub api_base: Option<String>,
+
+    #[serde(default)]
+    pub extra: ExtraConfig,
+}
+
+/// Provider-specific extras that don't belong on every client.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ExtraConfig {
+    /// A proxy URL, e.g. "socks5://127.0.0.1:1080" or "https://proxy:8443".
+    pub proxy: Option<String>,
+
+    /// Connect timeout in seconds.
+    pub connect_timeout: Option<u64>,
+}
+
+/// A named prompt preset, configured under `[roles.<name>]`. See
+/// [`crate::roles`] for how these combine with the built-in roles.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RoleConfig {
+    /// The system message to prepend to the conversation.
+    pub prompt: String,
+
+    /// Overrides the `--model` flag when this role is selected.
+    pub model: Option<String>,
+
+    /// Overrides the `--temperature` flag when this role is selected.
+    pub temperature: Option<f32>,
+}
+
+/// Settings for the `serve` subcommand, configured under `[serve]`.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ServeConfig {
+    /// Shared secret that callers must present as `Authorization: Bearer <token>`.
+    pub auth_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct Config {
+    #[serde(default)]
+    pub clients: Vec<ClientConfig>,
+
+    #[serde(default)]
+    pub roles: HashMap<String, RoleConfig>,
+
+    #[serde(default)]
+    pub serve: ServeConfig,
+}
+
+impl Config {
+    /// Returns `~/.config/ai-cli/config.toml` (or the platform equivalent).
+    pub fn config_path() -> Result<PathBuf> {
+        let base = dirs::config_dir().context("Could not determine the user config directory")?;
+        Ok(base.join("ai-cli").join("config.toml"))
+    }
+
+    /// Returns `~/.config/ai-cli/config.yaml`, the YAML sibling of [`Config::config_path`].
+    pub fn yaml_config_path() -> Result<PathBuf> {
+        let base = dirs::config_dir().context("Could not determine the user config directory")?;
+        Ok(base.join("ai-cli").join("config.yaml"))
+    }
+
+    /// Loads the config file if it exists, otherwise returns an empty config
+    /// so the CLI can still fall back to `AI_API_KEY`. Tries `config.toml`
+    /// first, then falls back to `config.yaml`.
+    pub fn load() -> Result<Config> {
+        let toml_path = Self::config_path()?;
+        if toml_path.exists() {
+            let raw = std::fs
+                ::read_to_string(&toml_path)
+                .with_context(|| format!("Failed to read config file at {}", toml_path.display()))?;
+
+            let config: Config = toml
+                ::from_str(&raw)
+                .with_context(|| format!("Failed to parse config file at {}", toml_path.display()))?;
+
+            return Ok(config);
+        }
+
+        let yaml_path = Self::yaml_config_path()?;
+        if yaml_path.exists() {
+            let raw = std::fs
+                ::read_to_string(&yaml_path)
+                .with_context(|| format!("Failed to read config file at {}", yaml_path.display()))?;
+
+            let config: Config = serde_yaml
+                ::from_str(&raw)
+                .with_context(|| format!("Failed to parse config file at {}", yaml_path.display()))?;
+
+            return Ok(config);
+        }
+
+        Ok(Config::default())
+    }
+
+    /// Looks up a configured client by name.
+    pub fn find_client(&self, name: &str) -> Option<&ClientConfig> {
+        self.clients.iter().find(|c| c.name == name)
+    }
+}
@@ -1,13 +1,25 @@
 use clap::{ Parser, Subcommand };
-use serde::{ Deserialize, Serialize };
 use anyhow::{ Context, Result };
-use reqwest::Client;
+use futures_util::StreamExt;
 use serde_json::json;
-use std::env;
 use colored::*;
 use std::io::{ self, Read };
 use thiserror::Error;
 
+mod client;
+mod config;
+mod roles;
+mod serve;
+mod sessions;
+mod tools;
+
+use client::{ Reply, SendData, ToolCall };
+use config::Config;
+use roles::Role;
+use serde_json::Value;
+use std::collections::HashMap;
+use tools::ToolRegistry;
+
 /// AI-powered CLI tool built in Rust
 #[derive(Parser)]
 #[command(name = "ai-cli")]
@@ -28,28 +40,6 @@ pub enum CliError {
     #[error("API returned error: {0}")] ApiError(String),
 }
 
-// #[derive(Subcommand)]
-// enum Commands {
-//     /// Ask a single question and get an AI-generated reply
-//     Ask {
-//         /// The prompt or question to send
-//         #[arg()]
-//         prompt: Option<String>,
-
-//         /// Model name (optional, defaults to gpt-4o-mini)
-//         #[arg(short, long, default_value = "gpt-4o-mini")]
-//         model: String,
-
-//         /// Max tokens (response length)
-//         #[arg(short = 'n', long, default_value_t = 150)]
-//         max_tokens: u32,
-
-//         /// Temperature (controls randomness)
-//         #[arg(short = 'T', long, default_value_t = 0.7)]
-//         temperature: f32,
-//     },
-// }
-
 #[derive(Subcommand)]
 enum Commands {
     /// Ask a single question and get an AI-generated reply
@@ -65,12 +55,32 @@ enum Commands {
 
         #[arg(short = 'T', long, default_value_t = 0.7)]
         temperature: f32,
+
+        /// Stream the reply token-by-token instead of waiting for the full response
+        #[arg(long, action = clap::ArgAction::SetTrue, overrides_with = "no_stream")]
+        stream: bool,
+
+        /// Disable streaming; if both --stream and --no-stream are passed, the last one wins
+        #[arg(long, action = clap::ArgAction::SetTrue, overrides_with = "stream")]
+        no_stream: bool,
+
+        /// Prepend a named prompt preset (see `--list-roles`) as a system message
+        #[arg(short, long)]
+        role: Option<String>,
+
+        /// List the available roles and exit
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        list_roles: bool,
     },
 
     /// Summarize text input
     Summarize {
         #[arg()]
         text: Option<String>,
+
+        /// Model to use as `client:model`, or a bare model name (optional, defaults to gpt-4o-mini)
+        #[arg(short, long, default_value = "gpt-4o-mini")]
+        model: String,
     },
 
     /// Translate text into another language
@@ -81,100 +91,121 @@ enum Commands {
         /// Target language (e.g., "fr", "es", "id")
         #[arg(short, long, default_value = "en")]
         to: String,
+
+        /// Model to use as `client:model`, or a bare model name (optional, defaults to gpt-4o-mini)
+        #[arg(short, long, default_value = "gpt-4o-mini")]
+        model: String,
     },
 
     /// Start an interactive chat session
     Chat {
         #[arg(short, long, default_value = "gpt-4o-mini")]
         model: String,
+
+        /// Prepend a named prompt preset (see `--list-roles`) as a system message
+        #[arg(short, long)]
+        role: Option<String>,
+
+        /// List the available roles and exit
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        list_roles: bool,
+
+        /// Resume (or start) a named session, saved under the config dir
+        #[arg(short, long)]
+        session: Option<String>,
+
+        /// List the available sessions and exit
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        list_sessions: bool,
     },
-}
 
-#[derive(Serialize)]
-struct ChatMessage<'a> {
-    role: &'a str,
-    content: &'a str,
+    /// Run a local OpenAI-compatible HTTP proxy backed by the configured clients
+    Serve {
+        /// Address to listen on (default 127.0.0.1:8080)
+        #[arg()]
+        addr: Option<String>,
+    },
 }
 
-#[derive(Serialize)]
-struct ChatRequest<'a> {
-    model: &'a str,
-    messages: Vec<ChatMessage<'a>>,
-    max_tokens: u32,
-    temperature: f32,
-}
+/// Drives a [`client::TokenStream`] to completion, printing each fragment as
+/// it arrives and flushing immediately, and returns the full accumulated reply.
+async fn print_stream(mut stream: client::TokenStream) -> Result<String> {
+    use std::io::Write;
+
+    let mut full_reply = String::new();
+    while let Some(fragment) = stream.next().await {
+        let fragment = fragment?;
+        print!("{}", fragment);
+        std::io::stdout().flush()?;
+        full_reply.push_str(&fragment);
+    }
+    println!();
 
-#[derive(Deserialize, Debug)]
-struct ChatChoice {
-    message: ChatMessageOwned,
+    Ok(full_reply)
 }
 
-#[derive(Deserialize, Debug)]
-struct ChatMessageOwned {
-    content: String,
-}
+/// Asks the controlling terminal (not stdin, which is commonly a pipe
+/// carrying the prompt itself) whether a side-effecting tool call may run.
+/// Fails loudly rather than silently declining if there's no TTY to ask.
+fn confirm_on_tty(prompt: &str) -> Result<bool> {
+    use std::io::{ BufRead, Write };
 
-#[derive(Deserialize, Debug)]
-struct ChatResponse {
-    choices: Vec<ChatChoice>,
-}
+    let mut tty = std::fs::File
+        ::open("/dev/tty")
+        .context("tool call requires confirmation, but there is no controlling TTY to ask (stdin may be a pipe); run interactively or without piped input")?;
 
-#[derive(Debug, Deserialize)]
-struct Choice {
-    message: Message,
-}
+    write!(tty, "{}", prompt)?;
+    tty.flush()?;
 
-#[derive(Debug, Deserialize)]
-struct Message {
-    content: String,
-}
+    let mut answer = String::new();
+    std::io::BufReader::new(tty).read_line(&mut answer)?;
 
-#[derive(Debug, Deserialize)]
-struct ApiResponse {
-    choices: Vec<Choice>,
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
 }
 
-async fn ask(
-    client: &Client,
-    api_key: &str,
-    model: &str,
-    prompt: &str,
-    max_tokens: u32,
-    temperature: f32
-) -> Result<String> {
-    let req = ChatRequest {
-        model,
-        messages: vec![ChatMessage {
-            role: "user",
-            content: prompt,
-        }],
-        max_tokens,
-        temperature,
+/// Runs a single tool call requested by the model, prompting the user for
+/// confirmation first if the tool is side-effecting (see [`tools::Tool::requires_confirmation`]).
+/// Unknown tools, tool errors and declined confirmations are returned as
+/// `{"error": ...}` so the conversation loop can hand the failure back to the
+/// model instead of aborting; failing to ask for confirmation (no TTY) is
+/// returned as an `Err`.
+async fn run_tool_call(tools: &ToolRegistry, call: &ToolCall) -> Result<Value> {
+    let Some(tool) = tools.get(&call.function.name) else {
+        return Ok(json!({ "error": format!("unknown tool '{}'", call.function.name) }));
     };
 
-    let url = "https://api.openai.com/v1/chat/completions";
+    if tool.requires_confirmation() {
+        let prompt = format!(
+            "Allow tool '{}' to run with args {}? [y/N] ",
+            tool.name(),
+            call.function.arguments
+        ).yellow().to_string();
 
-    let res = client
-        .post(url)
-        .bearer_auth(api_key)
-        .json(&req)
-        .send().await
-        .context("Failed to send request")?;
-
-    if !res.status().is_success() {
-        let status = res.status();
-        let body = res.text().await.unwrap_or_default();
-        anyhow::bail!("API error: {} - {}", status, body);
+        if !confirm_on_tty(&prompt)? {
+            return Ok(json!({ "error": "user declined to run this tool" }));
+        }
     }
 
-    let completion: ChatResponse = res.json().await.context("Failed to parse response")?;
+    let args: Value = serde_json::from_str(&call.function.arguments).unwrap_or(Value::Null);
+
+    Ok(
+        match tool.call(args).await {
+            Ok(result) => result,
+            Err(e) => json!({ "error": e.to_string() }),
+        }
+    )
+}
+
+/// Prints the name and system prompt of every available role, for `--list-roles`.
+fn print_roles(roles: &HashMap<String, Role>) {
+    println!("{}", "Available roles:".cyan().bold());
 
-    let reply = completion.choices
-        .get(0)
-        .map(|c| c.message.content.clone())
-        .unwrap_or_else(|| "No reply found".to_string());
+    let mut names: Vec<&String> = roles.keys().collect();
+    names.sort();
 
-    Ok(reply.trim().to_string())
+    for name in names {
+        println!("  {} - {}", name.green().bold(), roles[name].prompt);
+    }
 }
 
 fn get_input_or_stdin(opt: &Option<String>, prompt: &str) -> anyhow::Result<String> {
@@ -188,46 +219,33 @@ fn get_input_or_stdin(opt: &Option<String>, prompt: &str) -> anyhow::Result<Stri
     }
 }
 
-async fn send_ai_request(user_input: &str, task: &str, model: &str) -> anyhow::Result<()> {
-    let api_key = std::env::var("AI_API_KEY").map_err(|_| CliError::MissingApiKey)?;
-
+/// Sends a one-shot request under the given role: its prompt becomes the
+/// system message, and its `model`/`temperature` override the defaults.
+/// `model_spec` (the command's `--model` flag) is used unless the role
+/// itself pins a model via config.
+async fn send_ai_request(config: &Config, user_input: &str, role: &Role, model_spec: &str) -> anyhow::Result<()> {
+    let model = role.model.as_deref().unwrap_or(model_spec);
     log::debug!("Sending request with model {}", model);
 
-    let client = reqwest::Client::new();
-    let full_prompt = format!("{}\n\n{}", task, user_input);
-
-    let payload =
-        serde_json::json!({
-        "model": model,
-        "messages": [
-            { "role": "user", "content": full_prompt }
-        ],
-        "max_tokens": 200,
-        "temperature": 0.7
-    });
-
-    let res = client
-        .post("https://api.openai.com/v1/chat/completions")
-        .bearer_auth(&api_key)
-        .json(&payload)
-        .send().await
-        .map_err(|e| CliError::NetworkError(e.to_string()))?;
-
-    let status = res.status();
-    if !status.is_success() {
-        let err_text = res.text().await.unwrap_or_default();
-        return Err(CliError::ApiError(format!("{} - {}", status, err_text)).into());
-    }
+    let (client, model_name) = client::resolve_client(config, model)?;
 
-    let api_response: ApiResponse = res.json().await?;
+    let data = SendData {
+        model: model_name,
+        messages: vec![role.system_message(), json!({ "role": "user", "content": user_input })],
+        max_tokens: 200,
+        temperature: role.temperature.unwrap_or(0.7),
+        tools: vec![],
+    };
 
-    if let Some(choice) = api_response.choices.first() {
-        println!("{}", "================ AI Response ================".green().bold());
-        println!("{}", choice.message.content.white());
-        println!("{}", "============================================".green().bold());
-    } else {
-        println!("{}", "âš ï¸ No response received from AI.".yellow());
-    }
+    let reply = client.send_message(data).await.map_err(|e| CliError::NetworkError(e.to_string()))?;
+    let text = match reply {
+        Reply::Message(text) => text,
+        Reply::ToolCalls(_) => anyhow::bail!("received unexpected tool call (no tools were offered)"),
+    };
+
+    println!("{}", "================ AI Response ================".green().bold());
+    println!("{}", text.white());
+    println!("{}", "============================================".green().bold());
 
     Ok(())
 }
@@ -237,51 +255,173 @@ async fn main() -> anyhow::Result<()> {
     env_logger::init();
 
     let cli = Cli::parse();
+    let config = Config::load()?;
 
-    // match &cli.command {
-    //     Commands::Ask { prompt, model, max_tokens, temperature } => {
-    //         // Get prompt either from arg or stdin
-    //         let user_prompt = if let Some(p) = prompt {
-    //             p.clone()
-    //         } else {
-    //             println!("{}", "Enter your prompt (Ctrl+D to finish):".blue());
-    //             let mut buffer = String::new();
-    //             io::stdin().read_to_string(&mut buffer)?;
-    //             buffer
-    //         };
-
-    //         println!("Prompt: {}", user_prompt);
-    //         println!("Model: {}", model);
-    //         println!("Max tokens: {}", max_tokens);
-    //         println!("Temperature: {}", temperature);
-    //     }
-    // }
     match &cli.command {
-        Commands::Summarize { text } => {
+        Commands::Ask { prompt, model, max_tokens, temperature, stream, no_stream, role, list_roles } => {
+            let available_roles = roles::load_roles(&config);
+            if *list_roles {
+                print_roles(&available_roles);
+                return Ok(());
+            }
+
+            let selected_role = role
+                .as_ref()
+                .map(|name| {
+                    available_roles
+                        .get(name)
+                        .cloned()
+                        .ok_or_else(|| anyhow::anyhow!("No role named '{}' (see --list-roles)", name))
+                })
+                .transpose()?;
+
+            let model_spec = selected_role
+                .as_ref()
+                .and_then(|r| r.model.clone())
+                .unwrap_or_else(|| model.clone());
+            let temperature = selected_role.as_ref().and_then(|r| r.temperature).unwrap_or(*temperature);
+
+            let user_prompt = get_input_or_stdin(prompt, "Enter your prompt (Ctrl+D to finish):")?;
+            let (client, model_name) = client::resolve_client(&config, &model_spec)?;
+            let use_stream = *stream && !*no_stream;
+
+            let mut messages = vec![];
+            if let Some(role) = &selected_role {
+                messages.push(role.system_message());
+            }
+            messages.push(json!({ "role": "user", "content": user_prompt.trim() }));
+
+            println!("{}", "================ AI Response ================".green().bold());
+
+            if use_stream {
+                // Tool calling isn't supported over the streaming path.
+                let data = SendData {
+                    model: model_name,
+                    messages,
+                    max_tokens: *max_tokens,
+                    temperature,
+                    tools: vec![],
+                };
+                print_stream(client.send_message_stream(data).await?).await?;
+            } else {
+                let tools = ToolRegistry::built_ins();
+
+                loop {
+                    let data = SendData {
+                        model: model_name.clone(),
+                        messages: messages.clone(),
+                        max_tokens: *max_tokens,
+                        temperature,
+                        tools: tools.specs(),
+                    };
+
+                    match client.send_message(data).await? {
+                        Reply::Message(text) => {
+                            println!("{}", text.white());
+                            break;
+                        }
+                        Reply::ToolCalls(calls) => {
+                            messages.push(
+                                json!({
+                                "role": "assistant",
+                                "content": Value::Null,
+                                "tool_calls": calls.iter().map(|call| json!({
+                                    "id": call.id,
+                                    "type": "function",
+                                    "function": {
+                                        "name": call.function.name,
+                                        "arguments": call.function.arguments,
+                                    },
+                                })).collect::<Vec<_>>(),
+                            })
+                            );
+
+                            for call in &calls {
+                                let result = run_tool_call(&tools, call).await?;
+                                messages.push(
+                                    json!({
+                                    "role": "tool",
+                                    "tool_call_id": call.id,
+                                    "content": result.to_string(),
+                                })
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+
+            println!("{}", "============================================".green().bold());
+        }
+
+        Commands::Summarize { text, model } => {
             let input_text = get_input_or_stdin(
                 text,
                 "Paste text to summarize (Ctrl+D to finish):"
             )?;
-            send_ai_request(
-                &input_text,
-                "Summarize the following text briefly:",
-                "gpt-4o-mini"
-            ).await?;
+            let role = roles::load_roles(&config).remove("summarize").expect("built-in role must exist");
+            send_ai_request(&config, input_text.trim(), &role, model).await?;
         }
 
-        Commands::Translate { text, to } => {
+        Commands::Translate { text, to, model } => {
             let input_text = get_input_or_stdin(
                 text,
                 "Paste text to translate (Ctrl+D to finish):"
             )?;
-            let prompt = format!("Translate the following text into {}:", to);
-            send_ai_request(&input_text, &prompt, "gpt-4o-mini").await?;
+            let mut role = roles::load_roles(&config).remove("translate").expect("built-in role must exist");
+            role.prompt = role.prompt.replace("{lang}", to);
+            send_ai_request(&config, input_text.trim(), &role, model).await?;
         }
 
-        Commands::Chat { model } => {
+        Commands::Chat { model, role, list_roles, session, list_sessions } => {
+            if *list_sessions {
+                for name in sessions::list()? {
+                    println!("{}", name);
+                }
+                return Ok(());
+            }
+
+            let available_roles = roles::load_roles(&config);
+            if *list_roles {
+                print_roles(&available_roles);
+                return Ok(());
+            }
+
+            let selected_role = role
+                .as_ref()
+                .map(|name| {
+                    available_roles
+                        .get(name)
+                        .cloned()
+                        .ok_or_else(|| anyhow::anyhow!("No role named '{}' (see --list-roles)", name))
+                })
+                .transpose()?;
+
+            let model_spec = selected_role
+                .as_ref()
+                .and_then(|r| r.model.clone())
+                .unwrap_or_else(|| model.clone());
+            let temperature = selected_role.as_ref().and_then(|r| r.temperature).unwrap_or(0.7);
+
+            let mut history = match session {
+                Some(name) => sessions::load(name)?,
+                None => vec![],
+            };
+
+            // Seed a fresh session (or a plain, session-less chat) with the
+            // role's system message; a resumed session already has one saved.
+            if history.is_empty() {
+                if let Some(role) = &selected_role {
+                    history.push(role.system_message());
+                }
+            }
+
             println!("{}", "Starting interactive chat (type 'exit' to quit)".cyan().bold());
+            if let Some(name) = session {
+                println!("{}", format!("Resuming session '{}' ({} messages)", name, history.len()).cyan());
+            }
 
-            let mut history = vec![];
+            let (client, model_name) = client::resolve_client(&config, &model_spec)?;
 
             loop {
                 print!("{}", "You: ".blue().bold());
@@ -292,113 +432,59 @@ async fn main() -> anyhow::Result<()> {
                 std::io::stdin().read_line(&mut input)?;
                 let input = input.trim();
 
-                if input.eq_ignore_ascii_case("exit") {
+                if input.eq_ignore_ascii_case("exit") || input.eq_ignore_ascii_case(".exit") {
                     break;
                 }
 
+                if input.eq_ignore_ascii_case(".clear") {
+                    history.clear();
+                    if let Some(role) = &selected_role {
+                        history.push(role.system_message());
+                    }
+                    if let Some(name) = session {
+                        sessions::clear(name)?;
+                    }
+                    println!("{}", "Session cleared.".yellow());
+                    continue;
+                }
+
+                if input.eq_ignore_ascii_case(".save") {
+                    match session {
+                        Some(name) => {
+                            sessions::save(name, &history)?;
+                            println!("{}", format!("Saved session '{}'.", name).yellow());
+                        }
+                        None => println!("{}", "No active session; pass --session <name> to save one.".yellow()),
+                    }
+                    continue;
+                }
+
                 history.push(json!({ "role": "user", "content": input }));
 
-                let payload =
-                    json!({
-            "model": model,
-            "messages": history,
-            "max_tokens": 200,
-            "temperature": 0.7
-        });
-
-                let api_key = std::env::var("AI_API_KEY").expect("AI_API_KEY not set");
-                let client = reqwest::Client::new();
-                let res = client
-                    .post("https://api.openai.com/v1/chat/completions")
-                    .bearer_auth(api_key)
-                    .json(&payload)
-                    .send().await?;
-
-                let api_response: ApiResponse = res.json().await?;
-                if let Some(choice) = api_response.choices.first() {
-                    println!("{}", format!("AI: {}", choice.message.content).green());
-                    history.push(
-                        json!({
-                "role": "assistant",
-                "content": choice.message.content
-            })
-                    );
+                let data = SendData {
+                    model: model_name.clone(),
+                    messages: history.clone(),
+                    max_tokens: 200,
+                    temperature,
+                    tools: vec![],
+                };
+
+                print!("{}", "AI: ".green().bold());
+                io::stdout().flush()?;
+                let reply = print_stream(client.send_message_stream(data).await?).await?;
+                history.push(json!({ "role": "assistant", "content": reply }));
+
+                if let Some(name) = session {
+                    sessions::save(name, &history)?;
                 }
             }
         }
 
-        _ => {}
+        Commands::Serve { addr } => {
+            let addr = addr.clone().unwrap_or_else(|| "127.0.0.1:8080".to_string());
+            serve::run(config, &addr).await?;
+        }
     }
 
     Ok(())
 }
-
-// #[tokio::main]
-// async fn main() -> Result<()> {
-//     dotenvy::dotenv().ok();
-//     let cli = Cli::parse();
-
-//     let api_key = env
-//         ::var("AI_API_KEY")
-//         .context("Please set AI_API_KEY in your environment or .env file")?;
-
-//     let client = Client::new();
-
-//     match &cli.command {
-//         Commands::Ask { prompt, model, max_tokens, temperature } => {
-//             match ask(&client, &api_key, model, prompt, *max_tokens, *temperature).await {
-//                 Ok(reply) => println!("\nAI reply:\n{}\n", reply),
-//                 Err(e) => eprintln!("Error: {:?}", e),
-//             }
-//         }
-//     }
-
-//     Ok(())
-// }
-
-// #[tokio::main]
-// async fn main() -> anyhow::Result<()> {
-//     let cli = Cli::parse();
-
-//     match &cli.command {
-//         Commands::Ask { prompt, model, max_tokens, temperature } => {
-//             // Load API key
-//             let api_key = env::var("AI_API_KEY").expect("AI_API_KEY environment variable not set");
-
-//             // Prepare request payload
-//             let payload =
-//                 json!({
-//                 "model": model,
-//                 "messages": [
-//                     { "role": "user", "content": prompt }
-//                 ],
-//                 "max_tokens": max_tokens,
-//                 "temperature": temperature
-//             });
-
-//             // Send request
-//             let client = Client::new();
-//             let res = client
-//                 .post("https://api.openai.com/v1/chat/completions")
-//                 .bearer_auth(api_key)
-//                 .json(&payload)
-//                 .send().await?;
-
-//             if !res.status().is_success() {
-//                 let err_text = res.text().await?;
-//                 anyhow::bail!("API error: {} - {}", res.status(), err_text);
-//             }
-
-//             // Parse response
-//             let api_response: ApiResponse = res.json().await?;
-
-//             if let Some(choice) = api_response.choices.first() {
-//                 println!("\nðŸ¤– AI Response:\n{}\n", choice.message.content);
-//             } else {
-//                 println!("No response received from AI.");
-//             }
-//         }
-//     }
-
-//     Ok(())
-// }
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use serde_json::{ json, Value };
+
+use crate::config::Config;
+
+/// A named prompt preset: a system message optionally paired with a default
+/// model/temperature override, so a workflow (shell helper, summarizer, ...)
+/// can be selected with `--role <name>` instead of recompiling.
+#[derive(Debug, Clone)]
+pub struct Role {
+    pub prompt: String,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+}
+
+impl Role {
+    pub fn system_message(&self) -> Value {
+        json!({ "role": "system", "content": self.prompt })
+    }
+}
+
+/// Returns every available role: the built-ins (currently `summarize` and
+/// `translate`), overlaid with whatever the config file defines under
+/// `[roles.<name>]`. A config entry with a built-in's name replaces it.
+pub fn load_roles(config: &Config) -> HashMap<String, Role> {
+    let mut roles = built_ins();
+
+    for (name, role_config) in &config.roles {
+        roles.insert(name.clone(), Role {
+            prompt: role_config.prompt.clone(),
+            model: role_config.model.clone(),
+            temperature: role_config.temperature,
+        });
+    }
+
+    roles
+}
+
+fn built_ins() -> HashMap<String, Role> {
+    let mut roles = HashMap::new();
+
+    roles.insert("summarize".to_string(), Role {
+        prompt: "Summarize the following text briefly:".to_string(),
+        model: None,
+        temperature: None,
+    });
+
+    roles.insert("translate".to_string(), Role {
+        prompt: "Translate the following text into {lang}:".to_string(),
+        model: None,
+        temperature: None,
+    });
+
+    roles
+}
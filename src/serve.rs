@@ -0,0 +1,174 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::{ bail, Context, Result };
+use axum::extract::State;
+use axum::http::{ HeaderMap, StatusCode };
+use axum::response::sse::{ Event, Sse };
+use axum::response::{ IntoResponse, Response };
+use axum::routing::post;
+use axum::{ Json, Router };
+use futures_util::StreamExt;
+use serde::Deserialize;
+use serde_json::{ json, Value };
+
+use crate::client::{ self, Reply, SendData };
+use crate::config::Config;
+
+struct ServeState {
+    config: Config,
+    auth_token: String,
+}
+
+/// Runs the `serve` subcommand: an OpenAI-compatible `/v1/chat/completions`
+/// endpoint that forwards to whatever client `model` resolves to, so other
+/// tools can point at this CLI as a local gateway.
+///
+/// Requires `[serve] auth_token` to be set in the config file; callers must
+/// present it as `Authorization: Bearer <token>`. Without it the proxy would
+/// forward every request with the operator's configured provider credentials
+/// to whoever can reach the port.
+pub async fn run(config: Config, addr: &str) -> Result<()> {
+    let auth_token = config.serve.auth_token
+        .clone()
+        .context(
+            "serve requires an [serve] auth_token in the config file (see Config::config_path); refusing to start an unauthenticated proxy"
+        )?;
+
+    let addr: SocketAddr = addr.parse().with_context(|| format!("invalid address '{}'", addr))?;
+    let state = Arc::new(ServeState { config, auth_token });
+
+    let app = Router::new().route("/v1/chat/completions", post(chat_completions)).with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(addr).await.with_context(||
+        format!("failed to bind {}", addr)
+    )?;
+
+    println!("Listening on http://{}", addr);
+
+    axum::serve(listener, app).with_graceful_shutdown(shutdown_signal()).await?;
+
+    Ok(())
+}
+
+async fn shutdown_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Mirrors the subset of the OpenAI chat-completions request body this proxy
+/// understands. `messages` is forwarded as-is; any other request fields are
+/// ignored.
+#[derive(Deserialize)]
+struct IncomingRequest {
+    model: String,
+    messages: Vec<Value>,
+    #[serde(default = "default_max_tokens")]
+    max_tokens: u32,
+    #[serde(default = "default_temperature")]
+    temperature: f32,
+    #[serde(default)]
+    stream: bool,
+}
+
+fn default_max_tokens() -> u32 {
+    200
+}
+
+fn default_temperature() -> f32 {
+    0.7
+}
+
+/// Compares two strings in constant time, so a mismatching bearer token can't
+/// be brute-forced a byte at a time via response-time side channels.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.bytes()
+        .zip(b.bytes())
+        .fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Checks the `Authorization: Bearer <token>` header against the configured
+/// shared secret.
+fn authorize(state: &ServeState, headers: &HeaderMap) -> Result<()> {
+    let provided = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if constant_time_eq(token, &state.auth_token) => Ok(()),
+        _ => bail!("missing or invalid bearer token"),
+    }
+}
+
+async fn chat_completions(
+    State(state): State<Arc<ServeState>>,
+    headers: HeaderMap,
+    Json(req): Json<IncomingRequest>
+) -> Response {
+    if let Err(e) = authorize(&state, &headers) {
+        return (StatusCode::UNAUTHORIZED, Json(json!({ "error": { "message": e.to_string() } }))).into_response();
+    }
+
+    let (client, model_name) = match client::resolve_client(&state.config, &req.model) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            return error_response(&e.to_string());
+        }
+    };
+
+    let data = SendData {
+        model: model_name,
+        messages: req.messages,
+        max_tokens: req.max_tokens,
+        temperature: req.temperature,
+        tools: vec![],
+    };
+
+    if req.stream { stream_response(client, data).await } else { buffered_response(client, data).await }
+}
+
+async fn buffered_response(client: Box<dyn client::Client>, data: SendData) -> Response {
+    match client.send_message(data).await {
+        Ok(Reply::Message(text)) =>
+            Json(
+                json!({
+            "choices": [{ "message": { "role": "assistant", "content": text } }],
+        })
+            ).into_response(),
+        Ok(Reply::ToolCalls(_)) => error_response("tool calls are not supported by the serve proxy"),
+        Err(e) => error_response(&e.to_string()),
+    }
+}
+
+async fn stream_response(client: Box<dyn client::Client>, data: SendData) -> Response {
+    let token_stream = match client.send_message_stream(data).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            return error_response(&e.to_string());
+        }
+    };
+
+    let events = token_stream
+        .map(|chunk| {
+            let payload = match chunk {
+                Ok(content) => json!({ "choices": [{ "delta": { "content": content } }] }),
+                Err(e) => json!({ "error": { "message": e.to_string() } }),
+            };
+            Ok::<_, std::convert::Infallible>(Event::default().data(payload.to_string()))
+        })
+        .chain(
+            futures_util::stream::once(async {
+                Ok::<_, std::convert::Infallible>(Event::default().data("[DONE]"))
+            })
+        );
+
+    Sse::new(events).into_response()
+}
+
+fn error_response(message: &str) -> Response {
+    (StatusCode::BAD_GATEWAY, Json(json!({ "error": { "message": message } }))).into_response()
+}
@@ -0,0 +1,85 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{ bail, Context, Result };
+use serde_json::Value;
+
+use crate::config::Config;
+
+/// Validates that a session name is safe to use as a bare file name, rejecting
+/// anything that could escape the sessions directory (absolute paths, `..`, `/`).
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty() &&
+        name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+
+    if !valid {
+        bail!("invalid session name '{}': only letters, digits, '_' and '-' are allowed", name);
+    }
+
+    Ok(())
+}
+
+/// Returns `~/.config/ai-cli/sessions/`, creating it if necessary.
+fn sessions_dir() -> Result<PathBuf> {
+    let path = Config::config_path()?
+        .parent()
+        .context("config path has no parent directory")?
+        .join("sessions");
+
+    fs::create_dir_all(&path).with_context(|| format!("failed to create sessions dir at {}", path.display()))?;
+
+    Ok(path)
+}
+
+fn session_path(name: &str) -> Result<PathBuf> {
+    validate_name(name)?;
+    Ok(sessions_dir()?.join(format!("{}.json", name)))
+}
+
+/// Loads a session's saved messages, or an empty history if it hasn't been saved yet.
+pub fn load(name: &str) -> Result<Vec<Value>> {
+    let path = session_path(name)?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let raw = fs::read_to_string(&path).with_context(|| format!("failed to read session '{}'", name))?;
+    let messages: Vec<Value> = serde_json
+        ::from_str(&raw)
+        .with_context(|| format!("failed to parse session '{}'", name))?;
+
+    Ok(messages)
+}
+
+/// Writes a session's messages to disk, overwriting any previous save.
+pub fn save(name: &str, messages: &[Value]) -> Result<()> {
+    let path = session_path(name)?;
+    let raw = serde_json::to_string_pretty(messages)?;
+    fs::write(&path, raw).with_context(|| format!("failed to write session '{}'", name))?;
+    Ok(())
+}
+
+/// Deletes a session's saved file, if any.
+pub fn clear(name: &str) -> Result<()> {
+    let path = session_path(name)?;
+    if path.exists() {
+        fs::remove_file(&path).with_context(|| format!("failed to remove session '{}'", name))?;
+    }
+    Ok(())
+}
+
+/// Lists the names of all saved sessions.
+pub fn list() -> Result<Vec<String>> {
+    let dir = sessions_dir()?;
+    let mut names = vec![];
+
+    for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        if let Some(name) = entry.path().file_stem().and_then(|s| s.to_str()) {
+            names.push(name.to_string());
+        }
+    }
+
+    names.sort();
+    Ok(names)
+}
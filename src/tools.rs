@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::process::Command;
+
+use anyhow::{ anyhow, Context, Result };
+use async_trait::async_trait;
+use serde_json::{ json, Value };
+
+/// A function the model can ask to have run locally.
+///
+/// Tools whose [`Tool::name`] starts with `may_` perform a side effect
+/// (reaching the filesystem, spawning a process, ...) and must be confirmed
+/// by the user before [`Tool::call`] runs; see [`Tool::requires_confirmation`].
+#[async_trait]
+pub trait Tool: Send + Sync {
+    fn name(&self) -> &str;
+    fn description(&self) -> &str;
+    /// JSON-schema for this tool's arguments object.
+    fn schema(&self) -> Value;
+    async fn call(&self, args: Value) -> Result<Value>;
+}
+
+impl dyn Tool {
+    pub fn requires_confirmation(&self) -> bool {
+        self.name().starts_with("may_")
+    }
+
+    /// The OpenAI `{"type": "function", "function": {...}}` spec for this tool.
+    pub fn spec(&self) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": self.name(),
+                "description": self.description(),
+                "parameters": self.schema(),
+            }
+        })
+    }
+}
+
+/// Looks up tools by name and lists the specs to hand to the model.
+#[derive(Default)]
+pub struct ToolRegistry {
+    tools: HashMap<String, Box<dyn Tool>>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry seeded with the built-in tools (`get_time`, `may_read_file`,
+    /// `may_run_shell`).
+    pub fn built_ins() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(GetTimeTool));
+        registry.register(Box::new(MayReadFileTool));
+        registry.register(Box::new(MayRunShellTool));
+        registry
+    }
+
+    pub fn register(&mut self, tool: Box<dyn Tool>) {
+        self.tools.insert(tool.name().to_string(), tool);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Tool> {
+        self.tools.get(name).map(|t| t.as_ref())
+    }
+
+    pub fn specs(&self) -> Vec<Value> {
+        self.tools.values().map(|t| t.spec()).collect()
+    }
+}
+
+/// Returns the current local date and time.
+pub struct GetTimeTool;
+
+#[async_trait]
+impl Tool for GetTimeTool {
+    fn name(&self) -> &str {
+        "get_time"
+    }
+
+    fn description(&self) -> &str {
+        "Get the current local date and time."
+    }
+
+    fn schema(&self) -> Value {
+        json!({ "type": "object", "properties": {} })
+    }
+
+    async fn call(&self, _args: Value) -> Result<Value> {
+        Ok(json!({ "time": chrono::Local::now().to_rfc3339() }))
+    }
+}
+
+/// Reads a local file and returns its contents as text.
+pub struct MayReadFileTool;
+
+#[async_trait]
+impl Tool for MayReadFileTool {
+    fn name(&self) -> &str {
+        "may_read_file"
+    }
+
+    fn description(&self) -> &str {
+        "Read a local file and return its contents as text."
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Path to the file to read" },
+            },
+            "required": ["path"],
+        })
+    }
+
+    async fn call(&self, args: Value) -> Result<Value> {
+        let path = args["path"].as_str().ok_or_else(|| anyhow!("missing 'path' argument"))?;
+        let content = std::fs
+            ::read_to_string(path)
+            .with_context(|| format!("failed to read '{}'", path))?;
+        Ok(json!({ "content": content }))
+    }
+}
+
+/// Runs a shell command and returns its output.
+pub struct MayRunShellTool;
+
+#[async_trait]
+impl Tool for MayRunShellTool {
+    fn name(&self) -> &str {
+        "may_run_shell"
+    }
+
+    fn description(&self) -> &str {
+        "Run a shell command and return its stdout, stderr and exit code."
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "command": { "type": "string", "description": "The shell command to run" },
+            },
+            "required": ["command"],
+        })
+    }
+
+    async fn call(&self, args: Value) -> Result<Value> {
+        let command = args["command"].as_str().ok_or_else(|| anyhow!("missing 'command' argument"))?;
+
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .output()
+            .with_context(|| format!("failed to run '{}'", command))?;
+
+        Ok(
+            json!({
+            "stdout": String::from_utf8_lossy(&output.stdout),
+            "stderr": String::from_utf8_lossy(&output.stderr),
+            "exit_code": output.status.code(),
+        })
+        )
+    }
+}